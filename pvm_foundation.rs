@@ -1,33 +1,1165 @@
 //! PVM Foundation for Borglife DNA Encoding
 //!
 //! Basic Rust crate for PVM bytecode handling, foundations for Phase 1.
+//!
+//! ## Feature flags
+//!
+//! - `std` (default): standard library `HashMap` and formatting. Also
+//!   gates the container codec ([`PVMBytecode::encode`]/`decode`), which
+//!   depends on `flate2` for compression.
+//! - Disable default features (`default-features = false`) to build under
+//!   `#![no_std]`, using `alloc` for collections instead. The MessagePack
+//!   `Packable`/`Unpackable` implementation works either way.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+
+#[cfg(feature = "std")]
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+#[cfg(feature = "std")]
+use std::fmt;
+#[cfg(feature = "std")]
+use std::string::String;
+#[cfg(feature = "std")]
+use std::vec::Vec;
 
-use std::collections::HashMap;
+#[cfg(not(feature = "std"))]
+use alloc::collections::{BTreeMap, BTreeSet};
+#[cfg(not(feature = "std"))]
+use alloc::format;
+#[cfg(not(feature = "std"))]
+use alloc::string::{String, ToString};
+#[cfg(not(feature = "std"))]
+use alloc::vec;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+#[cfg(not(feature = "std"))]
+use core::fmt;
+
+/// `PVMBytecode::metadata`'s map type: a real `HashMap` under `std`, or a
+/// `BTreeMap` from `alloc` when built `no_std` (no hasher available there
+/// without pulling in an extra dependency).
+#[cfg(feature = "std")]
+pub type MetadataMap = HashMap<String, String>;
+#[cfg(not(feature = "std"))]
+pub type MetadataMap = BTreeMap<String, String>;
 
 /// Simple PVM bytecode representation
-#[derive(Debug, Clone)]
+///
+/// `consts8`/`consts16`/`consts32`/`consts64` are typed constant pools:
+/// `LoadConst*` instructions reference a value by its index into the
+/// pool matching their width, rather than inlining it as an immediate.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct PVMBytecode {
     pub opcodes: Vec<u8>,
-    pub metadata: HashMap<String, String>,
+    pub metadata: MetadataMap,
+    pub consts8: Vec<u8>,
+    pub consts16: Vec<i16>,
+    pub consts32: Vec<i32>,
+    pub consts64: Vec<i64>,
+}
+
+/// Magic marker identifying an encoded [`PVMBytecode`] container.
+#[cfg(feature = "std")]
+const CONTAINER_MAGIC: &[u8; 4] = b"PVMB";
+/// Current container format version, written by [`PVMBytecode::encode`] and
+/// checked by [`PVMBytecode::decode`]. Bumped to 2 when the typed constant
+/// pool sections were added.
+#[cfg(feature = "std")]
+const CONTAINER_VERSION: u8 = 2;
+
+#[cfg(feature = "std")]
+impl PVMBytecode {
+    /// Serialize this bytecode into the self-describing container format:
+    /// magic marker, format version, a length-prefixed metadata section,
+    /// the four count-prefixed constant pools (`consts8` to `consts64`),
+    /// and the opcode blob (optionally deflate-compressed, recorded by a
+    /// flag byte).
+    ///
+    /// The opcode blob is stored compressed only when that's actually
+    /// smaller than storing it raw.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(CONTAINER_MAGIC);
+        out.push(CONTAINER_VERSION);
+
+        let metadata_bytes = encode_metadata(&self.metadata);
+        out.extend_from_slice(&(metadata_bytes.len() as u32).to_le_bytes());
+        out.extend_from_slice(&metadata_bytes);
+
+        out.extend_from_slice(&encode_consts8(&self.consts8));
+        out.extend_from_slice(&encode_consts16(&self.consts16));
+        out.extend_from_slice(&encode_consts32(&self.consts32));
+        out.extend_from_slice(&encode_consts64(&self.consts64));
+
+        let compressed = deflate(&self.opcodes);
+        if compressed.len() < self.opcodes.len() {
+            out.push(1);
+            out.extend_from_slice(&compressed);
+        } else {
+            out.push(0);
+            out.extend_from_slice(&self.opcodes);
+        }
+
+        out
+    }
+
+    /// Parse a container produced by [`PVMBytecode::encode`].
+    ///
+    /// Returns an error if the magic marker doesn't match or the format
+    /// version isn't one this build understands.
+    pub fn decode(bytes: &[u8]) -> Result<PVMBytecode, String> {
+        if bytes.len() < CONTAINER_MAGIC.len() || &bytes[..CONTAINER_MAGIC.len()] != CONTAINER_MAGIC {
+            return Err("invalid container: bad magic marker".to_string());
+        }
+        let mut cursor = CONTAINER_MAGIC.len();
+
+        let version = *bytes
+            .get(cursor)
+            .ok_or_else(|| "truncated container: missing version byte".to_string())?;
+        if version != CONTAINER_VERSION {
+            return Err(format!("unsupported container version: {version}"));
+        }
+        cursor += 1;
+
+        let metadata_len = u32::from_le_bytes(
+            bytes
+                .get(cursor..cursor + 4)
+                .ok_or_else(|| "truncated container: missing metadata length".to_string())?
+                .try_into()
+                .unwrap(),
+        ) as usize;
+        cursor += 4;
+
+        let metadata_bytes = bytes
+            .get(cursor..cursor + metadata_len)
+            .ok_or_else(|| "truncated container: metadata section cut short".to_string())?;
+        let metadata = decode_metadata(metadata_bytes)?;
+        cursor += metadata_len;
+
+        let (consts8, consumed) = decode_consts8(&bytes[cursor..])?;
+        cursor += consumed;
+        let (consts16, consumed) = decode_consts16(&bytes[cursor..])?;
+        cursor += consumed;
+        let (consts32, consumed) = decode_consts32(&bytes[cursor..])?;
+        cursor += consumed;
+        let (consts64, consumed) = decode_consts64(&bytes[cursor..])?;
+        cursor += consumed;
+
+        let compressed = *bytes
+            .get(cursor)
+            .ok_or_else(|| "truncated container: missing compression flag".to_string())?;
+        cursor += 1;
+
+        let blob = &bytes[cursor..];
+        let opcodes = match compressed {
+            0 => blob.to_vec(),
+            1 => inflate(blob)?,
+            other => return Err(format!("invalid compression flag: {other}")),
+        };
+
+        Ok(PVMBytecode {
+            opcodes,
+            metadata,
+            consts8,
+            consts16,
+            consts32,
+            consts64,
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+fn encode_metadata(map: &MetadataMap) -> Vec<u8> {
+    let mut entries: Vec<(&String, &String)> = map.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut out = Vec::new();
+    out.extend_from_slice(&(entries.len() as u32).to_le_bytes());
+    for (key, value) in entries {
+        out.extend_from_slice(&(key.len() as u16).to_le_bytes());
+        out.extend_from_slice(key.as_bytes());
+        out.extend_from_slice(&(value.len() as u16).to_le_bytes());
+        out.extend_from_slice(value.as_bytes());
+    }
+    out
+}
+
+#[cfg(feature = "std")]
+fn decode_metadata(bytes: &[u8]) -> Result<MetadataMap, String> {
+    let truncated = || "truncated container: metadata entry cut short".to_string();
+    let count = u32::from_le_bytes(bytes.get(0..4).ok_or_else(truncated)?.try_into().unwrap()) as usize;
+
+    let mut map = MetadataMap::new();
+    let mut cursor = 4;
+    for _ in 0..count {
+        let key_len =
+            u16::from_le_bytes(bytes.get(cursor..cursor + 2).ok_or_else(truncated)?.try_into().unwrap()) as usize;
+        cursor += 2;
+        let key = String::from_utf8(bytes.get(cursor..cursor + key_len).ok_or_else(truncated)?.to_vec())
+            .map_err(|e| format!("invalid metadata key: {e}"))?;
+        cursor += key_len;
+
+        let value_len =
+            u16::from_le_bytes(bytes.get(cursor..cursor + 2).ok_or_else(truncated)?.try_into().unwrap()) as usize;
+        cursor += 2;
+        let value = String::from_utf8(bytes.get(cursor..cursor + value_len).ok_or_else(truncated)?.to_vec())
+            .map_err(|e| format!("invalid metadata value: {e}"))?;
+        cursor += value_len;
+
+        map.insert(key, value);
+    }
+    Ok(map)
+}
+
+#[cfg(feature = "std")]
+fn encode_consts8(values: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(&(values.len() as u32).to_le_bytes());
+    out.extend_from_slice(values);
+    out
+}
+
+/// Returns the decoded pool and how many bytes of `bytes` it consumed.
+#[cfg(feature = "std")]
+fn decode_consts8(bytes: &[u8]) -> Result<(Vec<u8>, usize), String> {
+    let truncated = || "truncated container: consts8 pool cut short".to_string();
+    let count = u32::from_le_bytes(bytes.get(0..4).ok_or_else(truncated)?.try_into().unwrap()) as usize;
+    let values = bytes.get(4..4 + count).ok_or_else(truncated)?.to_vec();
+    Ok((values, 4 + count))
+}
+
+#[cfg(feature = "std")]
+fn encode_consts16(values: &[i16]) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(&(values.len() as u32).to_le_bytes());
+    for v in values {
+        out.extend_from_slice(&v.to_le_bytes());
+    }
+    out
+}
+
+#[cfg(feature = "std")]
+fn decode_consts16(bytes: &[u8]) -> Result<(Vec<i16>, usize), String> {
+    let truncated = || "truncated container: consts16 pool cut short".to_string();
+    let count = u32::from_le_bytes(bytes.get(0..4).ok_or_else(truncated)?.try_into().unwrap()) as usize;
+    if count > bytes.len().saturating_sub(4) / 2 {
+        return Err(truncated());
+    }
+    let mut values = Vec::with_capacity(count);
+    let mut cursor = 4;
+    for _ in 0..count {
+        let raw = bytes.get(cursor..cursor + 2).ok_or_else(truncated)?;
+        values.push(i16::from_le_bytes(raw.try_into().unwrap()));
+        cursor += 2;
+    }
+    Ok((values, cursor))
+}
+
+#[cfg(feature = "std")]
+fn encode_consts32(values: &[i32]) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(&(values.len() as u32).to_le_bytes());
+    for v in values {
+        out.extend_from_slice(&v.to_le_bytes());
+    }
+    out
+}
+
+#[cfg(feature = "std")]
+fn decode_consts32(bytes: &[u8]) -> Result<(Vec<i32>, usize), String> {
+    let truncated = || "truncated container: consts32 pool cut short".to_string();
+    let count = u32::from_le_bytes(bytes.get(0..4).ok_or_else(truncated)?.try_into().unwrap()) as usize;
+    if count > bytes.len().saturating_sub(4) / 4 {
+        return Err(truncated());
+    }
+    let mut values = Vec::with_capacity(count);
+    let mut cursor = 4;
+    for _ in 0..count {
+        let raw = bytes.get(cursor..cursor + 4).ok_or_else(truncated)?;
+        values.push(i32::from_le_bytes(raw.try_into().unwrap()));
+        cursor += 4;
+    }
+    Ok((values, cursor))
+}
+
+#[cfg(feature = "std")]
+fn encode_consts64(values: &[i64]) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(&(values.len() as u32).to_le_bytes());
+    for v in values {
+        out.extend_from_slice(&v.to_le_bytes());
+    }
+    out
+}
+
+#[cfg(feature = "std")]
+fn decode_consts64(bytes: &[u8]) -> Result<(Vec<i64>, usize), String> {
+    let truncated = || "truncated container: consts64 pool cut short".to_string();
+    let count = u32::from_le_bytes(bytes.get(0..4).ok_or_else(truncated)?.try_into().unwrap()) as usize;
+    if count > bytes.len().saturating_sub(4) / 8 {
+        return Err(truncated());
+    }
+    let mut values = Vec::with_capacity(count);
+    let mut cursor = 4;
+    for _ in 0..count {
+        let raw = bytes.get(cursor..cursor + 8).ok_or_else(truncated)?;
+        values.push(i64::from_le_bytes(raw.try_into().unwrap()));
+        cursor += 8;
+    }
+    Ok((values, cursor))
+}
+
+#[cfg(feature = "std")]
+fn deflate(data: &[u8]) -> Vec<u8> {
+    use flate2::write::DeflateEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data).expect("writing to an in-memory buffer cannot fail");
+    encoder.finish().expect("finishing an in-memory buffer cannot fail")
+}
+
+#[cfg(feature = "std")]
+fn inflate(data: &[u8]) -> Result<Vec<u8>, String> {
+    use flate2::read::DeflateDecoder;
+    use std::io::Read;
+
+    let mut decoder = DeflateDecoder::new(data);
+    let mut out = Vec::new();
+    decoder
+        .read_to_end(&mut out)
+        .map_err(|e| format!("failed to inflate opcode blob: {e}"))?;
+    Ok(out)
+}
+
+/// A single decoded PVM instruction.
+///
+/// Each variant carries its own typed immediate operands (if any), decoded
+/// from the raw opcode stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Instruction {
+    Nop,
+    Push8(u8),
+    Push16(i16),
+    Push32(i32),
+    Push64(i64),
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Load(u8),
+    Store(u8),
+    Jmp(u16),
+    Jz(u16),
+    Jnz(u16),
+    Call(u16),
+    Ret,
+    Halt,
+    /// Push the value at `consts8[index]` onto the stack.
+    LoadConst8(u8),
+    /// Push the value at `consts16[index]` onto the stack.
+    LoadConst16(u8),
+    /// Push the value at `consts32[index]` onto the stack.
+    LoadConst32(u8),
+    /// Push the value at `consts64[index]` onto the stack.
+    LoadConst64(u8),
+    /// An opcode byte that doesn't match any known mnemonic.
+    Unknown(u8),
+}
+
+impl Instruction {
+    /// Total encoded length in bytes: the opcode byte plus any immediate
+    /// operands. Used by the disassembler to advance over variable-length
+    /// instructions. Never zero, so there's no meaningful `is_empty`.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        match self {
+            Instruction::Nop
+            | Instruction::Add
+            | Instruction::Sub
+            | Instruction::Mul
+            | Instruction::Div
+            | Instruction::Ret
+            | Instruction::Halt
+            | Instruction::Unknown(_) => 1,
+            Instruction::Push8(_)
+            | Instruction::Load(_)
+            | Instruction::Store(_)
+            | Instruction::LoadConst8(_)
+            | Instruction::LoadConst16(_)
+            | Instruction::LoadConst32(_)
+            | Instruction::LoadConst64(_) => 2,
+            Instruction::Push16(_)
+            | Instruction::Jmp(_)
+            | Instruction::Jz(_)
+            | Instruction::Jnz(_)
+            | Instruction::Call(_) => 3,
+            Instruction::Push32(_) => 5,
+            Instruction::Push64(_) => 9,
+        }
+    }
+
+    /// Decode a single instruction starting at `bytes[0]`.
+    ///
+    /// Returns an error if `bytes` is empty or too short to hold the
+    /// immediate operands the opcode requires.
+    fn decode(bytes: &[u8]) -> Result<Instruction, String> {
+        let opcode = *bytes
+            .first()
+            .ok_or_else(|| "unexpected end of bytecode while reading opcode".to_string())?;
+
+        let operand = |n: usize| -> Result<&[u8], String> {
+            if bytes.len() >= n {
+                Ok(&bytes[1..n])
+            } else {
+                Err(format!(
+                    "truncated operand for opcode {:#04x}: need {} byte(s), have {}",
+                    opcode,
+                    n - 1,
+                    bytes.len() - 1
+                ))
+            }
+        };
+
+        Ok(match opcode {
+            0x00 => Instruction::Nop,
+            0x01 => Instruction::Push8(operand(2)?[0]),
+            0x02 => {
+                let b = operand(3)?;
+                Instruction::Push16(i16::from_le_bytes([b[0], b[1]]))
+            }
+            0x03 => {
+                let b = operand(5)?;
+                Instruction::Push32(i32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            }
+            0x04 => {
+                let b = operand(9)?;
+                let mut buf = [0u8; 8];
+                buf.copy_from_slice(b);
+                Instruction::Push64(i64::from_le_bytes(buf))
+            }
+            0x05 => Instruction::Add,
+            0x06 => Instruction::Sub,
+            0x07 => Instruction::Mul,
+            0x08 => Instruction::Div,
+            0x09 => Instruction::Load(operand(2)?[0]),
+            0x0A => Instruction::Store(operand(2)?[0]),
+            0x0B => {
+                let b = operand(3)?;
+                Instruction::Jmp(u16::from_le_bytes([b[0], b[1]]))
+            }
+            0x0C => {
+                let b = operand(3)?;
+                Instruction::Jz(u16::from_le_bytes([b[0], b[1]]))
+            }
+            0x0D => {
+                let b = operand(3)?;
+                Instruction::Jnz(u16::from_le_bytes([b[0], b[1]]))
+            }
+            0x0E => {
+                let b = operand(3)?;
+                Instruction::Call(u16::from_le_bytes([b[0], b[1]]))
+            }
+            0x0F => Instruction::Ret,
+            0x10 => Instruction::Halt,
+            0x11 => Instruction::LoadConst8(operand(2)?[0]),
+            0x12 => Instruction::LoadConst16(operand(2)?[0]),
+            0x13 => Instruction::LoadConst32(operand(2)?[0]),
+            0x14 => Instruction::LoadConst64(operand(2)?[0]),
+            other => Instruction::Unknown(other),
+        })
+    }
+
+    /// Encode this instruction to its opcode byte plus immediate operands,
+    /// appending to `out`. Inverse of [`Instruction::decode`].
+    fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            Instruction::Nop => out.push(0x00),
+            Instruction::Push8(v) => {
+                out.push(0x01);
+                out.push(*v);
+            }
+            Instruction::Push16(v) => {
+                out.push(0x02);
+                out.extend_from_slice(&v.to_le_bytes());
+            }
+            Instruction::Push32(v) => {
+                out.push(0x03);
+                out.extend_from_slice(&v.to_le_bytes());
+            }
+            Instruction::Push64(v) => {
+                out.push(0x04);
+                out.extend_from_slice(&v.to_le_bytes());
+            }
+            Instruction::Add => out.push(0x05),
+            Instruction::Sub => out.push(0x06),
+            Instruction::Mul => out.push(0x07),
+            Instruction::Div => out.push(0x08),
+            Instruction::Load(reg) => {
+                out.push(0x09);
+                out.push(*reg);
+            }
+            Instruction::Store(reg) => {
+                out.push(0x0A);
+                out.push(*reg);
+            }
+            Instruction::Jmp(addr) => {
+                out.push(0x0B);
+                out.extend_from_slice(&addr.to_le_bytes());
+            }
+            Instruction::Jz(addr) => {
+                out.push(0x0C);
+                out.extend_from_slice(&addr.to_le_bytes());
+            }
+            Instruction::Jnz(addr) => {
+                out.push(0x0D);
+                out.extend_from_slice(&addr.to_le_bytes());
+            }
+            Instruction::Call(addr) => {
+                out.push(0x0E);
+                out.extend_from_slice(&addr.to_le_bytes());
+            }
+            Instruction::Ret => out.push(0x0F),
+            Instruction::Halt => out.push(0x10),
+            Instruction::LoadConst8(idx) => {
+                out.push(0x11);
+                out.push(*idx);
+            }
+            Instruction::LoadConst16(idx) => {
+                out.push(0x12);
+                out.push(*idx);
+            }
+            Instruction::LoadConst32(idx) => {
+                out.push(0x13);
+                out.push(*idx);
+            }
+            Instruction::LoadConst64(idx) => {
+                out.push(0x14);
+                out.push(*idx);
+            }
+            Instruction::Unknown(byte) => out.push(*byte),
+        }
+    }
+}
+
+/// An addressed listing of decoded instructions, keyed by the byte offset
+/// each instruction starts at. Offsets are preserved exactly so they can be
+/// used directly as jump targets.
+#[derive(Debug, Clone, Default)]
+pub struct Disassembly {
+    pub instructions: BTreeMap<usize, Instruction>,
+}
+
+impl Disassembly {
+    /// Look up the instruction decoded at byte offset `addr`, if any.
+    pub fn get(&self, addr: usize) -> Option<&Instruction> {
+        self.instructions.get(&addr)
+    }
+
+    /// Decode `bytes` into an addressed instruction map by walking the
+    /// stream from offset 0, advancing by each instruction's encoded
+    /// length.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Disassembly, String> {
+        let mut instructions = BTreeMap::new();
+        let mut offset = 0;
+        while offset < bytes.len() {
+            let instr = Instruction::decode(&bytes[offset..])?;
+            let len = instr.len();
+            instructions.insert(offset, instr);
+            offset += len;
+        }
+        Ok(Disassembly { instructions })
+    }
+
+    /// Decode a hex-encoded bytecode string (see [`Disassembly::from_bytes`]).
+    pub fn from_hex_str(hex_str: &str) -> Result<Disassembly, String> {
+        let bytes = hex::decode(hex_str).map_err(|e| format!("invalid hex: {e}"))?;
+        Disassembly::from_bytes(&bytes)
+    }
+
+    /// Resolve the inline annotation for every instruction in this listing,
+    /// keyed by offset: the register name for a `Load`/`Store`, the symbolic
+    /// name for a recognized pushed constant, or (when `pools` is supplied)
+    /// the resolved value of a constant-pool load.
+    pub fn annotate(&self, pools: Option<&PVMBytecode>) -> BTreeMap<usize, String> {
+        self.instructions
+            .iter()
+            .filter_map(|(&offset, instr)| annotate_instruction(instr, pools).map(|note| (offset, note)))
+            .collect()
+    }
+}
+
+/// Disassemble raw PVM bytecode into an addressed instruction map.
+pub fn disassemble(bytecode: &[u8]) -> Result<Disassembly, String> {
+    Disassembly::from_bytes(bytecode)
+}
+
+/// One byte range of a [`ControlFlowDisassembly`]: either a real instruction
+/// reached by execution, or a run of bytes not decoded as reachable code
+/// (never landed on by control flow, or too short to hold the instruction
+/// that starts there), which is treated as inline data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Region {
+    Code(Instruction),
+    Data(Vec<u8>),
+}
+
+/// A control-flow-following disassembly, produced by [`disassemble_from_entry`].
+///
+/// Unlike [`Disassembly`], which decodes every byte as an instruction,
+/// `regions` only contains [`Region::Code`] at offsets actually reachable
+/// from the entry point; everything else is collapsed into [`Region::Data`]
+/// runs.
+#[derive(Debug, Clone, Default)]
+pub struct ControlFlowDisassembly {
+    pub regions: BTreeMap<usize, Region>,
+    /// Short human-readable notes keyed by the offset they describe, e.g.
+    /// a resolved register name or a recognized constant value.
+    pub annotations: BTreeMap<usize, String>,
+}
+
+impl ControlFlowDisassembly {
+    pub fn get(&self, addr: usize) -> Option<&Region> {
+        self.regions.get(&addr)
+    }
+}
+
+/// Returns a short name for VM constants that show up often enough in
+/// practice to be worth calling out inline, or `None` for an ordinary
+/// value.
+fn known_constant_name(value: i64) -> Option<&'static str> {
+    match value {
+        0 => Some("ZERO"),
+        1 => Some("ONE"),
+        -1 => Some("NEG_ONE"),
+        _ => None,
+    }
+}
+
+/// Resolve the inline annotation for `instr`, if any: the register name for
+/// a `Load`/`Store`, the symbolic name for a recognized pushed constant, or
+/// (when `pools` is available) the resolved value of a constant-pool load.
+fn annotate_instruction(instr: &Instruction, pools: Option<&PVMBytecode>) -> Option<String> {
+    match instr {
+        Instruction::Load(reg) | Instruction::Store(reg) => Some(format!("reg r{reg}")),
+        Instruction::Push8(v) => known_constant_name(*v as i64).map(|name| format!("const {name}")),
+        Instruction::Push16(v) => known_constant_name(*v as i64).map(|name| format!("const {name}")),
+        Instruction::Push32(v) => known_constant_name(*v as i64).map(|name| format!("const {name}")),
+        Instruction::Push64(v) => known_constant_name(*v).map(|name| format!("const {name}")),
+        Instruction::LoadConst8(idx) => pools
+            .and_then(|p| p.consts8.get(*idx as usize))
+            .map(|v| format!("= {v}")),
+        Instruction::LoadConst16(idx) => pools
+            .and_then(|p| p.consts16.get(*idx as usize))
+            .map(|v| format!("= {v}")),
+        Instruction::LoadConst32(idx) => pools
+            .and_then(|p| p.consts32.get(*idx as usize))
+            .map(|v| format!("= {v}")),
+        Instruction::LoadConst64(idx) => pools
+            .and_then(|p| p.consts64.get(*idx as usize))
+            .map(|v| format!("= {v}")),
+        _ => None,
+    }
+}
+
+/// Disassemble `bytecode` starting from `entry`, following sequential
+/// fall-through and jump/call targets to discover which offsets are
+/// genuinely reached as code. Bytes never reached this way are reported as
+/// [`Region::Data`] rather than being guessed at as instructions.
+pub fn disassemble_from_entry(
+    bytecode: &[u8],
+    entry: usize,
+) -> Result<ControlFlowDisassembly, String> {
+    disassemble_from_entry_impl(bytecode, entry, None)
+}
+
+/// Like [`disassemble_from_entry`], but resolves `LoadConst*` instructions
+/// against `bytecode`'s own constant pools so the listing can annotate the
+/// value each one actually loads.
+pub fn disassemble_bytecode_from_entry(
+    bytecode: &PVMBytecode,
+    entry: usize,
+) -> Result<ControlFlowDisassembly, String> {
+    disassemble_from_entry_impl(&bytecode.opcodes, entry, Some(bytecode))
+}
+
+fn disassemble_from_entry_impl(
+    bytecode: &[u8],
+    entry: usize,
+    pools: Option<&PVMBytecode>,
+) -> Result<ControlFlowDisassembly, String> {
+    let mut regions: BTreeMap<usize, Region> = BTreeMap::new();
+    let mut annotations: BTreeMap<usize, String> = BTreeMap::new();
+    let mut worklist = vec![entry];
+    let mut visited: BTreeSet<usize> = BTreeSet::new();
+
+    while let Some(offset) = worklist.pop() {
+        if offset >= bytecode.len() || visited.contains(&offset) {
+            continue;
+        }
+        visited.insert(offset);
+
+        let instr = match Instruction::decode(&bytecode[offset..]) {
+            Ok(instr) => instr,
+            // Bytes we can't decode as an instruction are left unclassified
+            // here; the gap-fill pass below turns them into Data.
+            Err(_) => continue,
+        };
+        let len = instr.len();
+
+        if let Some(note) = annotate_instruction(&instr, pools) {
+            annotations.insert(offset, note);
+        }
+
+        let targets: Vec<usize> = match &instr {
+            Instruction::Jmp(addr) => vec![*addr as usize],
+            Instruction::Jz(addr) | Instruction::Jnz(addr) | Instruction::Call(addr) => {
+                vec![*addr as usize, offset + len]
+            }
+            Instruction::Ret | Instruction::Halt => vec![],
+            _ => vec![offset + len],
+        };
+        worklist.extend(targets);
+
+        regions.insert(offset, Region::Code(instr));
+    }
+
+    // Fill every byte range not claimed as code with Data regions so the
+    // whole bytecode stays covered.
+    let mut filled: BTreeMap<usize, Region> = BTreeMap::new();
+    let mut cursor = 0;
+    for (&offset, region) in &regions {
+        if offset < cursor {
+            return Err(format!(
+                "ambiguous control flow: instruction at offset {offset} overlaps a \
+                 previously decoded instruction ending at offset {cursor}"
+            ));
+        }
+        if cursor < offset {
+            filled.insert(cursor, Region::Data(bytecode[cursor..offset].to_vec()));
+        }
+        cursor = offset + region_len(region);
+        filled.insert(offset, region.clone());
+    }
+    if cursor < bytecode.len() {
+        filled.insert(cursor, Region::Data(bytecode[cursor..].to_vec()));
+    }
+
+    Ok(ControlFlowDisassembly {
+        regions: filled,
+        annotations,
+    })
+}
+
+fn region_len(region: &Region) -> usize {
+    match region {
+        Region::Code(instr) => instr.len(),
+        Region::Data(bytes) => bytes.len(),
+    }
+}
+
+/// Resolve a raw register index into its display name.
+fn register_name(reg: u8) -> String {
+    format!("r{reg}")
+}
+
+/// Break an instruction down into `(mnemonic, operands, comment)` so that
+/// [`fmt::Display`] and [`format_operations`] can render it identically.
+fn describe(instr: &Instruction) -> (&'static str, String, Option<&'static str>) {
+    match instr {
+        Instruction::Nop => ("nop", String::new(), None),
+        Instruction::Add => ("add", String::new(), None),
+        Instruction::Sub => ("sub", String::new(), None),
+        Instruction::Mul => ("mul", String::new(), None),
+        Instruction::Div => ("div", String::new(), None),
+        Instruction::Ret => ("ret", String::new(), None),
+        Instruction::Halt => ("halt", String::new(), None),
+        Instruction::Push8(v) => ("push8", v.to_string(), known_constant_name(*v as i64)),
+        Instruction::Push16(v) => ("push16", v.to_string(), known_constant_name(*v as i64)),
+        Instruction::Push32(v) => ("push32", v.to_string(), known_constant_name(*v as i64)),
+        Instruction::Push64(v) => ("push64", v.to_string(), known_constant_name(*v)),
+        Instruction::Load(reg) => ("load", register_name(*reg), None),
+        Instruction::Store(reg) => ("store", register_name(*reg), None),
+        Instruction::Jmp(addr) => ("jmp", format!("{addr:#06x}"), None),
+        Instruction::Jz(addr) => ("jz", format!("{addr:#06x}"), None),
+        Instruction::Jnz(addr) => ("jnz", format!("{addr:#06x}"), None),
+        Instruction::Call(addr) => ("call", format!("{addr:#06x}"), None),
+        Instruction::LoadConst8(idx) => ("ldc8", format!("pool[{idx}]"), None),
+        Instruction::LoadConst16(idx) => ("ldc16", format!("pool[{idx}]"), None),
+        Instruction::LoadConst32(idx) => ("ldc32", format!("pool[{idx}]"), None),
+        Instruction::LoadConst64(idx) => ("ldc64", format!("pool[{idx}]"), None),
+        Instruction::Unknown(byte) => ("???", format!("{byte:#04x}"), None),
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (mnemonic, operands, comment) = describe(self);
+        match (operands.is_empty(), comment) {
+            (true, _) => write!(f, "{mnemonic}"),
+            (false, Some(comment)) => write!(f, "{mnemonic:<8}{operands:<12} ; {comment}"),
+            (false, None) => write!(f, "{mnemonic:<8}{operands}"),
+        }
+    }
+}
+
+/// Render a clean, aligned textual listing of `instructions`: one line per
+/// instruction with its byte offset, mnemonic, decoded operands (registers
+/// by name, not bare index), and an inline comment for recognized
+/// constants.
+pub fn format_operations(instructions: &[Instruction]) -> String {
+    let mut out = String::new();
+    let mut offset = 0usize;
+    for instr in instructions {
+        out.push_str(&format!("{offset:08x}: {instr}\n"));
+        offset += instr.len();
+    }
+    out
+}
+
+/// Look up `value` in `pool`, appending it if it's not already present, and
+/// return its index. Errors if the pool would grow past 256 entries, since
+/// pool references are encoded as a single `u8`.
+fn intern<T: PartialEq>(pool: &mut Vec<T>, value: T) -> Result<u8, String> {
+    if let Some(idx) = pool.iter().position(|existing| *existing == value) {
+        return Ok(idx as u8);
+    }
+    if pool.len() > u8::MAX as usize {
+        return Err("constant pool is full: cannot index more than 256 entries".to_string());
+    }
+    pool.push(value);
+    Ok((pool.len() - 1) as u8)
 }
 
-/// Basic PVM disassembler (placeholder)
-pub fn disassemble(bytecode: &[u8]) -> Result<PVMBytecode, String> {
-    // Placeholder implementation
-    // In real implementation, use pvm-disassembler crate
-    let mut metadata = HashMap::new();
-    metadata.insert("length".to_string(), bytecode.len().to_string());
+/// Assemble raw PVM bytecode, interning `Push16`/`Push32`/`Push64`
+/// immediates into the appropriate constant pool and rewriting them to the
+/// matching `LoadConst*` instruction (deduplicating repeated values).
+/// `Push8` immediates are left inline, matching `LoadConst8`'s own width.
+pub fn assemble(opcodes: &[u8]) -> Result<PVMBytecode, String> {
+    let disassembly = Disassembly::from_bytes(opcodes)?;
+
+    let mut consts16 = Vec::new();
+    let mut consts32 = Vec::new();
+    let mut consts64 = Vec::new();
+    let mut out = Vec::new();
+
+    for instr in disassembly.instructions.values() {
+        match instr {
+            Instruction::Push16(v) => Instruction::LoadConst16(intern(&mut consts16, *v)?).encode(&mut out),
+            Instruction::Push32(v) => Instruction::LoadConst32(intern(&mut consts32, *v)?).encode(&mut out),
+            Instruction::Push64(v) => Instruction::LoadConst64(intern(&mut consts64, *v)?).encode(&mut out),
+            other => other.encode(&mut out),
+        }
+    }
 
     Ok(PVMBytecode {
-        opcodes: bytecode.to_vec(),
-        metadata,
+        opcodes: out,
+        consts16,
+        consts32,
+        consts64,
+        ..PVMBytecode::default()
     })
 }
 
-/// Basic PVM assembler (placeholder)
-pub fn assemble(opcodes: &[u8]) -> Result<Vec<u8>, String> {
-    // Placeholder implementation
-    Ok(opcodes.to_vec())
+/// Types that can serialize themselves to a MessagePack byte stream.
+pub trait Packable {
+    fn pack(&self) -> Result<Vec<u8>, String>;
+}
+
+/// Types that can be parsed back out of a MessagePack byte stream.
+pub trait Unpackable: Sized {
+    fn unpack(bytes: &[u8]) -> Result<Self, String>;
+}
+
+impl Packable for PVMBytecode {
+    /// Encode as a MessagePack map: `opcodes` and `consts8` as binary
+    /// blobs, `metadata` as a string-keyed map, and `consts16`/`consts32`/
+    /// `consts64` as arrays of signed integers.
+    ///
+    /// This is a small, dependency-light writer for exactly the shapes
+    /// this crate needs, not a general-purpose MessagePack encoder.
+    fn pack(&self) -> Result<Vec<u8>, String> {
+        let mut out = Vec::new();
+        write_map_header(&mut out, 6)?;
+
+        write_str(&mut out, "opcodes");
+        write_bin(&mut out, &self.opcodes)?;
+
+        write_str(&mut out, "metadata");
+        write_metadata_map(&mut out, &self.metadata)?;
+
+        write_str(&mut out, "consts8");
+        write_bin(&mut out, &self.consts8)?;
+
+        write_str(&mut out, "consts16");
+        let consts16: Vec<i64> = self.consts16.iter().map(|v| *v as i64).collect();
+        write_int_array(&mut out, &consts16)?;
+
+        write_str(&mut out, "consts32");
+        let consts32: Vec<i64> = self.consts32.iter().map(|v| *v as i64).collect();
+        write_int_array(&mut out, &consts32)?;
+
+        write_str(&mut out, "consts64");
+        write_int_array(&mut out, &self.consts64)?;
+
+        Ok(out)
+    }
+}
+
+impl Unpackable for PVMBytecode {
+    /// Parse a MessagePack byte stream produced by [`PVMBytecode::pack`].
+    fn unpack(bytes: &[u8]) -> Result<PVMBytecode, String> {
+        let mut cursor = 0;
+        let entries = read_map_header(bytes, &mut cursor)?;
+
+        let mut bytecode = PVMBytecode::default();
+        for _ in 0..entries {
+            let key = read_str(bytes, &mut cursor)?;
+            match key.as_str() {
+                "opcodes" => bytecode.opcodes = read_bin(bytes, &mut cursor)?,
+                "metadata" => bytecode.metadata = read_metadata_map(bytes, &mut cursor)?,
+                "consts8" => bytecode.consts8 = read_bin(bytes, &mut cursor)?,
+                "consts16" => {
+                    bytecode.consts16 = read_int_array(bytes, &mut cursor)?
+                        .into_iter()
+                        .map(|v| v as i16)
+                        .collect();
+                }
+                "consts32" => {
+                    bytecode.consts32 = read_int_array(bytes, &mut cursor)?
+                        .into_iter()
+                        .map(|v| v as i32)
+                        .collect();
+                }
+                "consts64" => bytecode.consts64 = read_int_array(bytes, &mut cursor)?,
+                other => return Err(format!("unknown msgpack key in PVMBytecode: {other}")),
+            }
+        }
+        Ok(bytecode)
+    }
+}
+
+fn write_map_header(out: &mut Vec<u8>, len: usize) -> Result<(), String> {
+    if len > u32::MAX as usize {
+        return Err("msgpack map has more entries than u32::MAX".to_string());
+    }
+    if len < 16 {
+        out.push(0x80 | len as u8);
+    } else if len <= 0xffff {
+        out.push(0xde);
+        out.extend_from_slice(&(len as u16).to_be_bytes());
+    } else {
+        out.push(0xdf);
+        out.extend_from_slice(&(len as u32).to_be_bytes());
+    }
+    Ok(())
+}
+
+fn write_array_header(out: &mut Vec<u8>, len: usize) -> Result<(), String> {
+    if len > u32::MAX as usize {
+        return Err("msgpack array has more entries than u32::MAX".to_string());
+    }
+    if len < 16 {
+        out.push(0x90 | len as u8);
+    } else if len <= 0xffff {
+        out.push(0xdc);
+        out.extend_from_slice(&(len as u16).to_be_bytes());
+    } else {
+        out.push(0xdd);
+        out.extend_from_slice(&(len as u32).to_be_bytes());
+    }
+    Ok(())
+}
+
+fn write_str(out: &mut Vec<u8>, s: &str) {
+    let bytes = s.as_bytes();
+    let len = bytes.len();
+    if len < 32 {
+        out.push(0xa0 | len as u8);
+    } else if len <= 0xff {
+        out.push(0xd9);
+        out.push(len as u8);
+    } else if len <= 0xffff {
+        out.push(0xda);
+        out.extend_from_slice(&(len as u16).to_be_bytes());
+    } else {
+        out.push(0xdb);
+        out.extend_from_slice(&(len as u32).to_be_bytes());
+    }
+    out.extend_from_slice(bytes);
+}
+
+fn write_bin(out: &mut Vec<u8>, data: &[u8]) -> Result<(), String> {
+    let len = data.len();
+    if len > u32::MAX as usize {
+        return Err("msgpack bin payload exceeds u32::MAX bytes".to_string());
+    }
+    if len <= 0xff {
+        out.push(0xc4);
+        out.push(len as u8);
+    } else if len <= 0xffff {
+        out.push(0xc5);
+        out.extend_from_slice(&(len as u16).to_be_bytes());
+    } else {
+        out.push(0xc6);
+        out.extend_from_slice(&(len as u32).to_be_bytes());
+    }
+    out.extend_from_slice(data);
+    Ok(())
+}
+
+fn write_int(out: &mut Vec<u8>, v: i64) {
+    out.push(0xd3);
+    out.extend_from_slice(&v.to_be_bytes());
+}
+
+fn write_int_array(out: &mut Vec<u8>, values: &[i64]) -> Result<(), String> {
+    write_array_header(out, values.len())?;
+    for v in values {
+        write_int(out, *v);
+    }
+    Ok(())
+}
+
+fn write_metadata_map(out: &mut Vec<u8>, map: &MetadataMap) -> Result<(), String> {
+    let mut entries: Vec<(&String, &String)> = map.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    write_map_header(out, entries.len())?;
+    for (key, value) in entries {
+        write_str(out, key);
+        write_str(out, value);
+    }
+    Ok(())
+}
+
+fn read_map_header(bytes: &[u8], cursor: &mut usize) -> Result<usize, String> {
+    let truncated = || "truncated msgpack: expected a map header".to_string();
+    let byte = *bytes.get(*cursor).ok_or_else(truncated)?;
+    *cursor += 1;
+    if byte & 0xf0 == 0x80 {
+        Ok((byte & 0x0f) as usize)
+    } else if byte == 0xde {
+        let len = u16::from_be_bytes(bytes.get(*cursor..*cursor + 2).ok_or_else(truncated)?.try_into().unwrap());
+        *cursor += 2;
+        Ok(len as usize)
+    } else if byte == 0xdf {
+        let len = u32::from_be_bytes(bytes.get(*cursor..*cursor + 4).ok_or_else(truncated)?.try_into().unwrap());
+        *cursor += 4;
+        Ok(len as usize)
+    } else {
+        Err(format!("expected msgpack map header, found byte {byte:#04x}"))
+    }
+}
+
+fn read_array_header(bytes: &[u8], cursor: &mut usize) -> Result<usize, String> {
+    let truncated = || "truncated msgpack: expected an array header".to_string();
+    let byte = *bytes.get(*cursor).ok_or_else(truncated)?;
+    *cursor += 1;
+    if byte & 0xf0 == 0x90 {
+        Ok((byte & 0x0f) as usize)
+    } else if byte == 0xdc {
+        let len = u16::from_be_bytes(bytes.get(*cursor..*cursor + 2).ok_or_else(truncated)?.try_into().unwrap());
+        *cursor += 2;
+        Ok(len as usize)
+    } else if byte == 0xdd {
+        let len = u32::from_be_bytes(bytes.get(*cursor..*cursor + 4).ok_or_else(truncated)?.try_into().unwrap());
+        *cursor += 4;
+        Ok(len as usize)
+    } else {
+        Err(format!("expected msgpack array header, found byte {byte:#04x}"))
+    }
+}
+
+fn read_str(bytes: &[u8], cursor: &mut usize) -> Result<String, String> {
+    let truncated = || "truncated msgpack: expected a string".to_string();
+    let byte = *bytes.get(*cursor).ok_or_else(truncated)?;
+    *cursor += 1;
+    let len = if byte & 0xe0 == 0xa0 {
+        (byte & 0x1f) as usize
+    } else if byte == 0xd9 {
+        let len = *bytes.get(*cursor).ok_or_else(truncated)? as usize;
+        *cursor += 1;
+        len
+    } else if byte == 0xda {
+        let len = u16::from_be_bytes(bytes.get(*cursor..*cursor + 2).ok_or_else(truncated)?.try_into().unwrap());
+        *cursor += 2;
+        len as usize
+    } else if byte == 0xdb {
+        let len = u32::from_be_bytes(bytes.get(*cursor..*cursor + 4).ok_or_else(truncated)?.try_into().unwrap());
+        *cursor += 4;
+        len as usize
+    } else {
+        return Err(format!("expected msgpack string, found byte {byte:#04x}"));
+    };
+    let raw = bytes.get(*cursor..*cursor + len).ok_or_else(truncated)?;
+    let s = String::from_utf8(raw.to_vec()).map_err(|e| format!("invalid utf-8 in msgpack string: {e}"))?;
+    *cursor += len;
+    Ok(s)
+}
+
+fn read_bin(bytes: &[u8], cursor: &mut usize) -> Result<Vec<u8>, String> {
+    let truncated = || "truncated msgpack: expected a bin payload".to_string();
+    let byte = *bytes.get(*cursor).ok_or_else(truncated)?;
+    *cursor += 1;
+    let len = if byte == 0xc4 {
+        let len = *bytes.get(*cursor).ok_or_else(truncated)? as usize;
+        *cursor += 1;
+        len
+    } else if byte == 0xc5 {
+        let len = u16::from_be_bytes(bytes.get(*cursor..*cursor + 2).ok_or_else(truncated)?.try_into().unwrap());
+        *cursor += 2;
+        len as usize
+    } else if byte == 0xc6 {
+        let len = u32::from_be_bytes(bytes.get(*cursor..*cursor + 4).ok_or_else(truncated)?.try_into().unwrap());
+        *cursor += 4;
+        len as usize
+    } else {
+        return Err(format!("expected msgpack bin, found byte {byte:#04x}"));
+    };
+    let data = bytes.get(*cursor..*cursor + len).ok_or_else(truncated)?.to_vec();
+    *cursor += len;
+    Ok(data)
+}
+
+fn read_int(bytes: &[u8], cursor: &mut usize) -> Result<i64, String> {
+    let truncated = || "truncated msgpack: expected an int64".to_string();
+    let byte = *bytes.get(*cursor).ok_or_else(truncated)?;
+    *cursor += 1;
+    if byte != 0xd3 {
+        return Err(format!("expected msgpack int64, found byte {byte:#04x}"));
+    }
+    let raw = bytes.get(*cursor..*cursor + 8).ok_or_else(truncated)?;
+    let mut buf = [0u8; 8];
+    buf.copy_from_slice(raw);
+    *cursor += 8;
+    Ok(i64::from_be_bytes(buf))
+}
+
+fn read_int_array(bytes: &[u8], cursor: &mut usize) -> Result<Vec<i64>, String> {
+    let len = read_array_header(bytes, cursor)?;
+    if len > bytes.len().saturating_sub(*cursor) / 9 {
+        return Err("truncated msgpack: int array longer than remaining input".to_string());
+    }
+    let mut values = Vec::with_capacity(len);
+    for _ in 0..len {
+        values.push(read_int(bytes, cursor)?);
+    }
+    Ok(values)
+}
+
+fn read_metadata_map(bytes: &[u8], cursor: &mut usize) -> Result<MetadataMap, String> {
+    let entries = read_map_header(bytes, cursor)?;
+    let mut map = MetadataMap::new();
+    for _ in 0..entries {
+        let key = read_str(bytes, cursor)?;
+        let value = read_str(bytes, cursor)?;
+        map.insert(key, value);
+    }
+    Ok(map)
 }
 
 #[cfg(test)]
@@ -36,9 +1168,248 @@ mod tests {
 
     #[test]
     fn test_round_trip() {
-        let original = vec![0x01, 0x02, 0x03];
+        let original = vec![0x05, 0x01, 0x2a, 0x0f];
         let disassembled = disassemble(&original).unwrap();
-        let assembled = assemble(&disassembled.opcodes).unwrap();
-        assert_eq!(original, assembled);
+        assert_eq!(disassembled.get(0), Some(&Instruction::Add));
+        assert_eq!(disassembled.get(1), Some(&Instruction::Push8(0x2a)));
+        assert_eq!(disassembled.get(3), Some(&Instruction::Ret));
+        let assembled = assemble(&original).unwrap();
+        assert_eq!(original, assembled.opcodes);
+    }
+
+    #[test]
+    fn test_assemble_interns_and_dedups_pool_constants() {
+        // Push32(10), Push32(20), Push32(10), Ret: the repeated 10 should
+        // share a single consts32 slot.
+        let source = vec![
+            0x03, 0x0a, 0x00, 0x00, 0x00, 0x03, 0x14, 0x00, 0x00, 0x00, 0x03, 0x0a, 0x00, 0x00,
+            0x00, 0x0f,
+        ];
+        let assembled = assemble(&source).unwrap();
+        assert_eq!(assembled.consts32, vec![10, 20]);
+
+        let disassembled = Disassembly::from_bytes(&assembled.opcodes).unwrap();
+        assert_eq!(disassembled.get(0), Some(&Instruction::LoadConst32(0)));
+        assert_eq!(disassembled.get(2), Some(&Instruction::LoadConst32(1)));
+        assert_eq!(disassembled.get(4), Some(&Instruction::LoadConst32(0)));
+        assert_eq!(disassembled.get(6), Some(&Instruction::Ret));
+    }
+
+    #[test]
+    fn test_disassembly_annotate_resolves_pool_constants() {
+        let bytecode = PVMBytecode {
+            opcodes: vec![0x13, 0x00],
+            consts32: vec![42],
+            ..PVMBytecode::default()
+        };
+        let disassembled = Disassembly::from_bytes(&bytecode.opcodes).unwrap();
+        let annotations = disassembled.annotate(Some(&bytecode));
+        assert_eq!(annotations.get(&0), Some(&"= 42".to_string()));
+    }
+
+    #[test]
+    fn test_disassemble_variable_length() {
+        // Jmp 0x0010 followed by Halt at offset 3.
+        let bytecode = vec![0x0B, 0x10, 0x00, 0x10];
+        let disassembled = disassemble(&bytecode).unwrap();
+        assert_eq!(disassembled.get(0), Some(&Instruction::Jmp(0x0010)));
+        assert_eq!(disassembled.get(3), Some(&Instruction::Halt));
+    }
+
+    #[test]
+    fn test_disassemble_truncated_operand() {
+        let bytecode = vec![0x02, 0x01];
+        assert!(disassemble(&bytecode).is_err());
+    }
+
+    #[test]
+    fn test_from_hex_str() {
+        let disassembled = Disassembly::from_hex_str("0f").unwrap();
+        assert_eq!(disassembled.get(0), Some(&Instruction::Ret));
+    }
+
+    #[test]
+    fn test_unknown_opcode() {
+        let disassembled = disassemble(&[0xEE]).unwrap();
+        assert_eq!(disassembled.get(0), Some(&Instruction::Unknown(0xEE)));
+    }
+
+    #[test]
+    fn test_disassemble_from_entry_skips_embedded_data() {
+        // Jmp 7 skips straight over 4 bytes of inline data to a Halt; a
+        // naive decoder would mis-decode those data bytes as instructions.
+        let mut bytecode = vec![0x0B, 0x07, 0x00]; // Jmp 0x0007
+        bytecode.extend_from_slice(&[0xDE, 0xAD, 0xBE, 0xEF]); // inline data
+        bytecode.push(0x10); // Halt at offset 7
+        let cfg = disassemble_from_entry(&bytecode, 0).unwrap();
+        assert_eq!(cfg.get(0), Some(&Region::Code(Instruction::Jmp(7))));
+        assert_eq!(cfg.get(7), Some(&Region::Code(Instruction::Halt)));
+        match cfg.get(3) {
+            Some(Region::Data(bytes)) => assert_eq!(bytes.len(), 4),
+            other => panic!("expected data region at offset 3, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_disassemble_from_entry_detects_overlapping_jump_target() {
+        // Push32 spans offsets 0..5, but the Jmp at offset 5 targets offset
+        // 3, landing inside the Push32's immediate bytes — an ambiguous
+        // overlap that must be reported, not silently resolved either way.
+        let bytecode = vec![0x03, 0x01, 0x02, 0x03, 0x04, 0x0B, 0x03, 0x00];
+        let result = disassemble_from_entry(&bytecode, 0);
+        assert!(result.is_err());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_disassemble_from_entry_annotates_registers_and_constants() {
+        let bytecode = vec![0x09, 0x02, 0x01, 0x00, 0x0F]; // Load r2; Push8 0; Ret
+        let cfg = disassemble_from_entry(&bytecode, 0).unwrap();
+        assert_eq!(cfg.annotations.get(&0), Some(&"reg r2".to_string()));
+        assert_eq!(cfg.annotations.get(&2), Some(&"const ZERO".to_string()));
+    }
+
+    #[test]
+    fn test_display_resolves_register_names_and_constants() {
+        assert_eq!(Instruction::Load(3).to_string(), "load    r3");
+        assert_eq!(
+            Instruction::Push8(0).to_string(),
+            "push8   0            ; ZERO"
+        );
+        assert_eq!(Instruction::Ret.to_string(), "ret");
+    }
+
+    #[test]
+    fn test_format_operations_tracks_offsets() {
+        let instructions = vec![Instruction::Jmp(4), Instruction::Halt];
+        let listing = format_operations(&instructions);
+        let mut lines = listing.lines();
+        assert_eq!(lines.next().unwrap(), "00000000: jmp     0x0004");
+        assert_eq!(lines.next().unwrap(), "00000003: halt");
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_container_round_trip() {
+        let mut metadata = MetadataMap::new();
+        metadata.insert("name".to_string(), "demo".to_string());
+        metadata.insert("length".to_string(), "3".to_string());
+        let original = PVMBytecode {
+            opcodes: vec![0x05, 0x01, 0x2a],
+            metadata,
+            consts8: vec![7, 8],
+            consts16: vec![-100, 100],
+            consts32: vec![-1000, 1000],
+            consts64: vec![i64::MIN, i64::MAX],
+        };
+
+        let encoded = original.encode();
+        assert_eq!(&encoded[..4], CONTAINER_MAGIC);
+        let decoded = PVMBytecode::decode(&encoded).unwrap();
+        assert_eq!(decoded, original);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_container_compresses_repetitive_opcodes() {
+        let bytecode = PVMBytecode {
+            opcodes: vec![0x00; 256],
+            ..Default::default()
+        };
+        let encoded = bytecode.encode();
+        assert!(encoded.len() < bytecode.opcodes.len());
+        let decoded = PVMBytecode::decode(&encoded).unwrap();
+        assert_eq!(decoded.opcodes, bytecode.opcodes);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_decode_rejects_bad_magic() {
+        let err = PVMBytecode::decode(&[0, 1, 2, 3, 4]).unwrap_err();
+        assert!(err.contains("magic"));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_decode_rejects_unknown_version() {
+        let mut bytes = CONTAINER_MAGIC.to_vec();
+        bytes.push(0xFF);
+        let err = PVMBytecode::decode(&bytes).unwrap_err();
+        assert!(err.contains("version"));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_decode_consts_rejects_oversized_count_without_aborting() {
+        // A count of u32::MAX would ask for gigabytes of capacity up front;
+        // it must be rejected as truncated input instead of aborting.
+        let huge_count = 0xFFFF_FFFFu32.to_le_bytes().to_vec();
+        assert!(decode_consts16(&huge_count).is_err());
+        assert!(decode_consts32(&huge_count).is_err());
+        assert!(decode_consts64(&huge_count).is_err());
+    }
+
+    #[test]
+    fn test_decode_const_pool_instructions() {
+        let bytecode = vec![0x11, 0x00, 0x12, 0x01, 0x13, 0x02, 0x14, 0x03];
+        let disassembled = disassemble(&bytecode).unwrap();
+        assert_eq!(disassembled.get(0), Some(&Instruction::LoadConst8(0)));
+        assert_eq!(disassembled.get(2), Some(&Instruction::LoadConst16(1)));
+        assert_eq!(disassembled.get(4), Some(&Instruction::LoadConst32(2)));
+        assert_eq!(disassembled.get(6), Some(&Instruction::LoadConst64(3)));
+    }
+
+    #[test]
+    fn test_disassemble_bytecode_from_entry_resolves_pool_constants() {
+        let bytecode = PVMBytecode {
+            opcodes: vec![0x11, 0x02, 0x0F], // LoadConst8 pool[2]; Ret
+            consts8: vec![10, 20, 30],
+            ..Default::default()
+        };
+        let cfg = disassemble_bytecode_from_entry(&bytecode, 0).unwrap();
+        assert_eq!(cfg.annotations.get(&0), Some(&"= 30".to_string()));
+    }
+
+    #[test]
+    fn test_msgpack_round_trip() {
+        let mut metadata = MetadataMap::new();
+        metadata.insert("name".to_string(), "demo".to_string());
+        let original = PVMBytecode {
+            opcodes: vec![0x05, 0x01, 0x2a],
+            metadata,
+            consts8: vec![1, 2, 3],
+            consts16: vec![-100, 100],
+            consts32: vec![-1000, 1000],
+            consts64: vec![i64::MIN, i64::MAX],
+        };
+
+        let packed = original.pack().unwrap();
+        let unpacked = PVMBytecode::unpack(&packed).unwrap();
+        assert_eq!(unpacked, original);
+    }
+
+    #[test]
+    fn test_msgpack_unpack_rejects_unknown_key() {
+        let mut bytes = Vec::new();
+        write_map_header(&mut bytes, 1).unwrap();
+        write_str(&mut bytes, "bogus");
+        write_bin(&mut bytes, &[]).unwrap();
+        let err = PVMBytecode::unpack(&bytes).unwrap_err();
+        assert!(err.contains("bogus"));
+    }
+
+    #[test]
+    fn test_msgpack_unpack_rejects_truncated_input() {
+        let err = PVMBytecode::unpack(&[0x81]).unwrap_err();
+        assert!(err.contains("truncated"));
+    }
+
+    #[test]
+    fn test_read_int_array_rejects_oversized_len_without_aborting() {
+        // Array header claiming u32::MAX int64 entries with no data behind
+        // it; must be rejected as truncated input instead of aborting.
+        let mut bytes = vec![0xdd];
+        bytes.extend_from_slice(&0xFFFF_FFFFu32.to_be_bytes());
+        let mut cursor = 0;
+        assert!(read_int_array(&bytes, &mut cursor).is_err());
+    }
+}